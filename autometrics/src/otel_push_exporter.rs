@@ -1,12 +1,28 @@
+use opentelemetry::KeyValue;
+#[cfg(feature = "otel-push-exporter-grpc")]
+use opentelemetry_otlp::tonic::metadata as tonic_metadata;
+#[cfg(feature = "otel-push-exporter-grpc")]
+use opentelemetry_otlp::tonic::transport;
 use opentelemetry_otlp::{
     ExportConfig, ExporterBuildError, MetricExporter, Protocol, WithExportConfig,
     OTEL_EXPORTER_OTLP_TIMEOUT_DEFAULT,
 };
-use opentelemetry_sdk::metrics::{MeterProviderBuilder, PeriodicReader, SdkMeterProvider};
+use opentelemetry_sdk::metrics::{
+    MeterProviderBuilder, PeriodicReader, SdkMeterProvider, Temporality,
+};
+use opentelemetry_sdk::resource::EnvResourceDetector;
+use opentelemetry_sdk::Resource;
+use std::collections::HashMap;
 use std::ops::Deref;
 use std::time::Duration;
 
 /// Newtype struct holding a [`SdkMeterProvider`] with a custom `Drop` implementation to automatically clean up itself
+///
+/// Note on diagnostics: a periodic push export failing (collector down, auth rejected, timed
+/// out) is reported through `opentelemetry`/`opentelemetry_sdk`'s own `otel_error!`/`otel_warn!`
+/// macros, not through any API in this crate. To see these, two things are required: enable the
+/// `internal-logs` Cargo feature on `opentelemetry`/`opentelemetry_sdk`/`opentelemetry_otlp`, and
+/// install a `tracing` subscriber in your application. Without both, export failures are silent.
 #[repr(transparent)]
 #[must_use = "Assign this to a unused variable instead: `let _meter = ...` (NOT `let _ = ...`), as else it will be dropped immediately - which will cause it to be shut down"]
 pub struct OtelMeterProvider(SdkMeterProvider);
@@ -26,12 +42,254 @@ impl Drop for OtelMeterProvider {
     }
 }
 
+/// TLS / transport security settings for the gRPC push exporter.
+///
+/// [`TlsConfig::default()`] is secure with no custom certificates. A [`PushExporterBuilder`]
+/// instead starts from the environment: `OTEL_EXPORTER_OTLP_METRICS_INSECURE` (falling back to
+/// `OTEL_EXPORTER_OTLP_INSECURE`), `OTEL_EXPORTER_OTLP_CERTIFICATE`,
+/// `OTEL_EXPORTER_OTLP_CLIENT_CERTIFICATE` and `OTEL_EXPORTER_OTLP_CLIENT_KEY` (each with a
+/// `_METRICS_` signal-specific override), per the OTLP spec.
+#[derive(Clone, Debug, Default)]
+pub struct TlsConfig {
+    insecure: bool,
+    ca_cert_path: Option<String>,
+    client_cert_path: Option<String>,
+    client_key_path: Option<String>,
+}
+
+impl TlsConfig {
+    /// Disable client transport security. Required when pointing a gRPC exporter at a
+    /// plaintext collector (no scheme on the endpoint).
+    pub fn with_insecure(mut self, insecure: bool) -> Self {
+        self.insecure = insecure;
+        self
+    }
+
+    /// Path to a PEM-encoded CA certificate used to verify the collector.
+    pub fn with_ca_cert(mut self, path: impl Into<String>) -> Self {
+        self.ca_cert_path = Some(path.into());
+        self
+    }
+
+    /// Paths to a PEM-encoded client certificate and private key, for mTLS.
+    pub fn with_client_cert(
+        mut self,
+        cert_path: impl Into<String>,
+        key_path: impl Into<String>,
+    ) -> Self {
+        self.client_cert_path = Some(cert_path.into());
+        self.client_key_path = Some(key_path.into());
+        self
+    }
+}
+
+/// Builder for customizing the OTLP push exporter beyond what the environment variables
+/// (and the plain `init_http`/`init_grpc` functions) provide.
+///
+/// Both transports already read `OTEL_EXPORTER_OTLP_HEADERS`/`OTEL_EXPORTER_OTLP_METRICS_HEADERS`
+/// themselves at `build()` time, and that env-sourced value wins over whatever is configured
+/// here for the same key. Use [`PushExporterBuilder::with_headers`]/[`with_header`][Self::with_header]
+/// for literal values you don't want to (or can't) set through the environment.
+#[derive(Clone, Debug)]
+pub struct PushExporterBuilder {
+    headers: HashMap<String, String>,
+    temporality: Temporality,
+    resource: Option<Resource>,
+    tls_config: TlsConfig,
+}
+
+impl Default for PushExporterBuilder {
+    fn default() -> Self {
+        Self {
+            headers: HashMap::new(),
+            temporality: temporality_from_env_or_default(),
+            resource: None,
+            tls_config: tls_config_from_env(),
+        }
+    }
+}
+
+impl PushExporterBuilder {
+    /// Create a new builder with no headers set and the temporality taken from
+    /// `OTEL_EXPORTER_OTLP_METRICS_TEMPORALITY_PREFERENCE` (cumulative by default).
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace the full set of headers / gRPC metadata sent with every export request.
+    ///
+    /// Values are used verbatim (not percent-decoded) — they're for literal values, not ones
+    /// lifted from an `OTEL_EXPORTER_OTLP_*_HEADERS` string. A key also present in the
+    /// environment is overridden by the environment's value.
+    pub fn with_headers(mut self, headers: HashMap<String, String>) -> Self {
+        self.headers = headers;
+        self
+    }
+
+    /// Add (or override) a single header / gRPC metadata entry.
+    ///
+    /// See [`with_headers`][Self::with_headers] for how this interacts with env-sourced headers.
+    pub fn with_header(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.insert(key.into(), value.into());
+        self
+    }
+
+    /// Set the temporality (delta vs. cumulative) used for every instrument.
+    ///
+    /// Defaults to [`Temporality::Cumulative`], overridden by
+    /// `OTEL_EXPORTER_OTLP_METRICS_TEMPORALITY_PREFERENCE` if set.
+    pub fn with_temporality(mut self, temporality: Temporality) -> Self {
+        self.temporality = temporality;
+        self
+    }
+
+    /// Attach a [`Resource`] (e.g. `service.name`, `service.version`, deployment attributes)
+    /// to every series exported by this meter provider.
+    ///
+    /// This is merged over the attributes detected from `OTEL_RESOURCE_ATTRIBUTES` and
+    /// `OTEL_SERVICE_NAME`, taking precedence over them.
+    pub fn with_resource(mut self, resource: Resource) -> Self {
+        self.resource = Some(resource);
+        self
+    }
+
+    /// Override the TLS / transport security settings (insecure flag, CA cert, client cert
+    /// and key for mTLS) used by the gRPC exporter.
+    ///
+    /// Defaults are parsed from the standard `OTEL_EXPORTER_OTLP_*` environment variables.
+    pub fn with_tls_config(mut self, tls_config: TlsConfig) -> Self {
+        self.tls_config = tls_config;
+        self
+    }
+
+    #[cfg(feature = "otel-push-exporter-grpc")]
+    fn client_tls_config(&self) -> Result<Option<transport::ClientTlsConfig>, ExporterBuildError> {
+        if self.tls_config.insecure {
+            return Ok(None);
+        }
+
+        let mut config = transport::ClientTlsConfig::new().with_native_roots();
+
+        if let Some(ca_cert_path) = &self.tls_config.ca_cert_path {
+            let ca_cert = std::fs::read(ca_cert_path)
+                .map_err(|e| ExporterBuildError::InvalidConfiguration(e.to_string()))?;
+            config = config.ca_certificate(transport::Certificate::from_pem(ca_cert));
+        }
+
+        if let (Some(cert_path), Some(key_path)) = (
+            &self.tls_config.client_cert_path,
+            &self.tls_config.client_key_path,
+        ) {
+            let cert = std::fs::read(cert_path)
+                .map_err(|e| ExporterBuildError::InvalidConfiguration(e.to_string()))?;
+            let key = std::fs::read(key_path)
+                .map_err(|e| ExporterBuildError::InvalidConfiguration(e.to_string()))?;
+            config = config.identity(transport::Identity::from_pem(cert, key));
+        }
+
+        Ok(Some(config))
+    }
+
+    fn resolved_resource(&self) -> Resource {
+        let mut builder = Resource::builder().with_detector(Box::new(EnvResourceDetector::new()));
+        if let Some(resource) = &self.resource {
+            builder = builder.with_attributes(
+                resource
+                    .iter()
+                    .map(|(k, v)| KeyValue::new(k.clone(), v.clone())),
+            );
+        }
+        builder.build()
+    }
+
+    /// Build the OTLP push exporter using HTTP transport with customized `timeout` and `period`.
+    ///
+    /// Transport security follows the `url` scheme (`http://` vs `https://`); CA/client
+    /// certificate configuration only applies to [`PushExporterBuilder::init_grpc`].
+    #[cfg(feature = "otel-push-exporter-http")]
+    pub fn init_http(
+        &self,
+        url: impl Into<String>,
+        timeout: Duration,
+        period: Duration,
+    ) -> Result<OtelMeterProvider, ExporterBuildError> {
+        let exporter = MetricExporter::builder()
+            .with_http()
+            .with_headers(self.headers.clone())
+            .with_temporality(self.temporality)
+            .with_export_config(ExportConfig {
+                endpoint: Some(url.into()),
+                protocol: Protocol::HttpBinary,
+                timeout: Some(timeout),
+                ..Default::default()
+            })
+            .build()?;
+
+        let reader = PeriodicReader::builder(exporter)
+            .with_interval(period)
+            .build();
+
+        Ok(OtelMeterProvider(
+            runtime()
+                .with_resource(self.resolved_resource())
+                .with_reader(reader)
+                .build(),
+        ))
+    }
+
+    /// Build the OTLP push exporter using gRPC transport with customized `timeout` and `period`.
+    #[cfg(feature = "otel-push-exporter-grpc")]
+    pub fn init_grpc(
+        &self,
+        url: impl Into<String>,
+        timeout: Duration,
+        period: Duration,
+    ) -> Result<OtelMeterProvider, ExporterBuildError> {
+        let metadata = metadata_map_from_headers(&self.headers);
+
+        let mut builder = MetricExporter::builder()
+            .with_tonic()
+            .with_metadata(metadata)
+            .with_temporality(self.temporality);
+
+        if let Some(tls_config) = self.client_tls_config()? {
+            builder = builder.with_tls_config(tls_config);
+        }
+
+        let exporter = builder
+            .with_export_config(ExportConfig {
+                endpoint: Some(url.into()),
+                protocol: Protocol::HttpBinary,
+                timeout: Some(timeout),
+                ..Default::default()
+            })
+            .build()?;
+
+        let reader = PeriodicReader::builder(exporter)
+            .with_interval(period)
+            .build();
+
+        Ok(OtelMeterProvider(
+            runtime()
+                .with_resource(self.resolved_resource())
+                .with_reader(reader)
+                .build(),
+        ))
+    }
+}
+
 /// Initialize the OpenTelemetry push exporter using HTTP transport.
 ///
 /// # Interval and timeout
 /// This function uses the environment variables `OTEL_METRIC_EXPORT_TIMEOUT` and `OTEL_METRIC_EXPORT_INTERVAL`
 /// to configure the timeout and interval respectively. If you want to customize those
 /// from within code, consider using [`init_http_with_timeout_period`].
+///
+/// # Authentication
+/// Headers (e.g. for bearer tokens or API keys) are picked up automatically from
+/// `OTEL_EXPORTER_OTLP_HEADERS` by the underlying OTLP exporter itself. To set literal
+/// values from within code instead, use [`PushExporterBuilder`] (env values still win on a
+/// conflicting key).
 #[cfg(feature = "otel-push-exporter-http")]
 pub fn init_http(url: impl Into<String>) -> Result<OtelMeterProvider, ExporterBuildError> {
     let (timeout, period) = timeout_and_period_from_env_or_default();
@@ -39,27 +297,16 @@ pub fn init_http(url: impl Into<String>) -> Result<OtelMeterProvider, ExporterBu
 }
 
 /// Initialize the OpenTelemetry push exporter using HTTP transport with customized `timeout` and `period`.
+///
+/// Headers are picked up automatically from `OTEL_EXPORTER_OTLP_HEADERS`. To set them from
+/// within code, use [`PushExporterBuilder::init_http`] instead.
 #[cfg(feature = "otel-push-exporter-http")]
 pub fn init_http_with_timeout_period(
     url: impl Into<String>,
     timeout: Duration,
     period: Duration,
 ) -> Result<OtelMeterProvider, ExporterBuildError> {
-    let exporter = MetricExporter::builder()
-        .with_http()
-        .with_export_config(ExportConfig {
-            endpoint: Some(url.into()),
-            protocol: Protocol::HttpBinary,
-            timeout: Some(timeout),
-            ..Default::default()
-        })
-        .build()?;
-
-    let reader = PeriodicReader::builder(exporter)
-        .with_interval(period)
-        .build();
-
-    Ok(OtelMeterProvider(runtime().with_reader(reader).build()))
+    PushExporterBuilder::new().init_http(url, timeout, period)
 }
 
 /// Initialize the OpenTelemetry push exporter using gRPC transport.
@@ -68,6 +315,12 @@ pub fn init_http_with_timeout_period(
 /// This function uses the environment variables `OTEL_METRIC_EXPORT_TIMEOUT` and `OTEL_METRIC_EXPORT_INTERVAL`
 /// to configure the timeout and interval respectively. If you want to customize those
 /// from within code, consider using [`init_grpc_with_timeout_period`].
+///
+/// # Authentication
+/// Headers (e.g. for bearer tokens or API keys) are picked up automatically from
+/// `OTEL_EXPORTER_OTLP_HEADERS` by the underlying OTLP exporter itself. To set literal
+/// values from within code instead, use [`PushExporterBuilder`] (env values still win on a
+/// conflicting key).
 #[cfg(feature = "otel-push-exporter-grpc")]
 pub fn init_grpc(url: impl Into<String>) -> Result<OtelMeterProvider, ExporterBuildError> {
     let (timeout, period) = timeout_and_period_from_env_or_default();
@@ -75,47 +328,114 @@ pub fn init_grpc(url: impl Into<String>) -> Result<OtelMeterProvider, ExporterBu
 }
 
 /// Initialize the OpenTelemetry push exporter using gRPC transport with customized `timeout` and `period`.
+///
+/// Headers are picked up automatically from `OTEL_EXPORTER_OTLP_HEADERS`. To set them from
+/// within code, use [`PushExporterBuilder::init_grpc`] instead.
 #[cfg(feature = "otel-push-exporter-grpc")]
 pub fn init_grpc_with_timeout_period(
     url: impl Into<String>,
     timeout: Duration,
     period: Duration,
 ) -> Result<OtelMeterProvider, ExporterBuildError> {
-    let exporter = MetricExporter::builder()
-        .with_tonic()
-        .with_export_config(ExportConfig {
-            endpoint: Some(url.into()),
-            protocol: Protocol::HttpBinary,
-            timeout: Some(timeout),
-            ..Default::default()
+    PushExporterBuilder::new().init_grpc(url, timeout, period)
+}
+
+/// Reads `OTEL_EXPORTER_OTLP_METRICS_TEMPORALITY_PREFERENCE` and maps it onto a
+/// [`Temporality`], defaulting to [`Temporality::Cumulative`] when unset or unrecognized.
+fn temporality_from_env_or_default() -> Temporality {
+    const OTEL_EXPORTER_OTLP_METRICS_TEMPORALITY_PREFERENCE_ENV: &str =
+        "OTEL_EXPORTER_OTLP_METRICS_TEMPORALITY_PREFERENCE";
+
+    std::env::var(OTEL_EXPORTER_OTLP_METRICS_TEMPORALITY_PREFERENCE_ENV)
+        .ok()
+        .and_then(|value| match value.to_lowercase().as_str() {
+            "cumulative" => Some(Temporality::Cumulative),
+            "delta" => Some(Temporality::Delta),
+            "lowmemory" => Some(Temporality::LowMemory),
+            _ => None,
         })
-        .build()?;
+        .unwrap_or(Temporality::Cumulative)
+}
+
+/// Reads the TLS-related `OTEL_EXPORTER_OTLP_*` environment variables (preferring the
+/// `_METRICS_`-specific signal override over the general one, per the OTLP spec).
+fn tls_config_from_env() -> TlsConfig {
+    fn env_bool(specific: &str, general: &str) -> bool {
+        std::env::var(specific)
+            .or_else(|_| std::env::var(general))
+            .map(|v| v.eq_ignore_ascii_case("true"))
+            .unwrap_or(false)
+    }
 
-    let reader = PeriodicReader::builder(exporter)
-        .with_interval(period)
-        .build();
+    fn env_path(specific: &str, general: &str) -> Option<String> {
+        std::env::var(specific)
+            .or_else(|_| std::env::var(general))
+            .ok()
+    }
+
+    let mut config = TlsConfig::default().with_insecure(env_bool(
+        "OTEL_EXPORTER_OTLP_METRICS_INSECURE",
+        "OTEL_EXPORTER_OTLP_INSECURE",
+    ));
+
+    if let Some(ca_cert_path) = env_path(
+        "OTEL_EXPORTER_OTLP_METRICS_CERTIFICATE",
+        "OTEL_EXPORTER_OTLP_CERTIFICATE",
+    ) {
+        config = config.with_ca_cert(ca_cert_path);
+    }
 
-    Ok(OtelMeterProvider(runtime().with_reader(reader).build()))
+    if let (Some(cert_path), Some(key_path)) = (
+        env_path(
+            "OTEL_EXPORTER_OTLP_METRICS_CLIENT_CERTIFICATE",
+            "OTEL_EXPORTER_OTLP_CLIENT_CERTIFICATE",
+        ),
+        env_path(
+            "OTEL_EXPORTER_OTLP_METRICS_CLIENT_KEY",
+            "OTEL_EXPORTER_OTLP_CLIENT_KEY",
+        ),
+    ) {
+        config = config.with_client_cert(cert_path, key_path);
+    }
+
+    config
+}
+
+#[cfg(feature = "otel-push-exporter-grpc")]
+fn metadata_map_from_headers(headers: &HashMap<String, String>) -> tonic_metadata::MetadataMap {
+    let mut metadata = tonic_metadata::MetadataMap::new();
+    for (key, value) in headers {
+        if let (Ok(key), Ok(value)) = (
+            tonic_metadata::MetadataKey::from_bytes(key.as_bytes()),
+            value.parse(),
+        ) {
+            metadata.insert(key, value);
+        }
+    }
+    metadata
 }
 
 /// returns timeout and period from their respective environment variables
 /// or the default, if they are not set or set to an invalid value
+///
+/// Per the OTLP spec, both `OTEL_METRIC_EXPORT_TIMEOUT` and `OTEL_METRIC_EXPORT_INTERVAL` are
+/// expressed in milliseconds.
 fn timeout_and_period_from_env_or_default() -> (Duration, Duration) {
     const OTEL_EXPORTER_TIMEOUT_ENV: &str = "OTEL_METRIC_EXPORT_TIMEOUT";
     const OTEL_EXPORTER_INTERVAL_ENV: &str = "OTEL_METRIC_EXPORT_INTERVAL";
 
-    let timeout = Duration::from_secs(
+    let timeout = Duration::from_millis(
         std::env::var_os(OTEL_EXPORTER_TIMEOUT_ENV)
             .and_then(|os_string| os_string.into_string().ok())
             .and_then(|str| str.parse().ok())
-            .unwrap_or(OTEL_EXPORTER_OTLP_TIMEOUT_DEFAULT.as_secs()),
+            .unwrap_or(OTEL_EXPORTER_OTLP_TIMEOUT_DEFAULT.as_millis() as u64),
     );
 
-    let period = Duration::from_secs(
+    let period = Duration::from_millis(
         std::env::var_os(OTEL_EXPORTER_INTERVAL_ENV)
             .and_then(|os_string| os_string.into_string().ok())
             .and_then(|str| str.parse().ok())
-            .unwrap_or(60),
+            .unwrap_or(60_000),
     );
 
     (timeout, period)
@@ -124,3 +444,83 @@ fn timeout_and_period_from_env_or_default() -> (Duration, Duration) {
 fn runtime() -> MeterProviderBuilder {
     SdkMeterProvider::builder()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// Serializes tests that read/write process environment variables, since `cargo test`
+    /// runs tests in the same process concurrently by default.
+    static ENV_LOCK: Mutex<()> = Mutex::new(());
+
+    #[test]
+    fn temporality_from_env_parses_known_values() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        std::env::set_var("OTEL_EXPORTER_OTLP_METRICS_TEMPORALITY_PREFERENCE", "delta");
+
+        assert_eq!(temporality_from_env_or_default(), Temporality::Delta);
+
+        std::env::remove_var("OTEL_EXPORTER_OTLP_METRICS_TEMPORALITY_PREFERENCE");
+    }
+
+    #[test]
+    fn temporality_from_env_defaults_to_cumulative_when_unset_or_unknown() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        std::env::remove_var("OTEL_EXPORTER_OTLP_METRICS_TEMPORALITY_PREFERENCE");
+        assert_eq!(temporality_from_env_or_default(), Temporality::Cumulative);
+
+        std::env::set_var("OTEL_EXPORTER_OTLP_METRICS_TEMPORALITY_PREFERENCE", "bogus");
+        assert_eq!(temporality_from_env_or_default(), Temporality::Cumulative);
+
+        std::env::remove_var("OTEL_EXPORTER_OTLP_METRICS_TEMPORALITY_PREFERENCE");
+    }
+
+    #[test]
+    fn tls_config_from_env_prefers_metrics_specific_insecure_var() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        std::env::set_var("OTEL_EXPORTER_OTLP_INSECURE", "false");
+        std::env::set_var("OTEL_EXPORTER_OTLP_METRICS_INSECURE", "true");
+
+        assert!(tls_config_from_env().insecure);
+
+        std::env::remove_var("OTEL_EXPORTER_OTLP_INSECURE");
+        std::env::remove_var("OTEL_EXPORTER_OTLP_METRICS_INSECURE");
+    }
+
+    #[test]
+    fn tls_config_from_env_defaults_to_secure_when_unset() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        std::env::remove_var("OTEL_EXPORTER_OTLP_INSECURE");
+        std::env::remove_var("OTEL_EXPORTER_OTLP_METRICS_INSECURE");
+
+        assert!(!tls_config_from_env().insecure);
+    }
+
+    #[test]
+    fn timeout_and_period_from_env_parses_milliseconds() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        std::env::set_var("OTEL_METRIC_EXPORT_TIMEOUT", "10000");
+        std::env::set_var("OTEL_METRIC_EXPORT_INTERVAL", "5000");
+
+        let (timeout, period) = timeout_and_period_from_env_or_default();
+
+        assert_eq!(timeout, Duration::from_secs(10));
+        assert_eq!(period, Duration::from_secs(5));
+
+        std::env::remove_var("OTEL_METRIC_EXPORT_TIMEOUT");
+        std::env::remove_var("OTEL_METRIC_EXPORT_INTERVAL");
+    }
+
+    #[test]
+    fn timeout_and_period_from_env_defaults_when_unset() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        std::env::remove_var("OTEL_METRIC_EXPORT_TIMEOUT");
+        std::env::remove_var("OTEL_METRIC_EXPORT_INTERVAL");
+
+        let (timeout, period) = timeout_and_period_from_env_or_default();
+
+        assert_eq!(timeout, OTEL_EXPORTER_OTLP_TIMEOUT_DEFAULT);
+        assert_eq!(period, Duration::from_secs(60));
+    }
+}